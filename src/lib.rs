@@ -1,17 +1,19 @@
 use anyhow::{Context, Error};
 use std::{
+    collections::HashSet,
     env,
     ffi::OsStr,
     fs::{self, File},
     io::{self, Write as _},
     path::{Path, PathBuf},
+    rc::Rc,
 };
 use walkdir::WalkDir;
 
 mod html;
 pub use html::StaticHtml;
 mod markdown;
-pub use markdown::StaticMarkdown;
+pub use markdown::{LinkRewriter, StaticMarkdown, WikilinkResolver};
 
 type Result<T = (), E = Error> = std::result::Result<T, E>;
 
@@ -39,6 +41,7 @@ type Result<T = (), E = Error> = std::result::Result<T, E>;
 pub struct Convertor {
     path: PathBuf,
     trim_html: bool,
+    wikilinks: bool,
 }
 
 impl Convertor {
@@ -46,6 +49,7 @@ impl Convertor {
         Self {
             path: path.as_ref().to_owned(),
             trim_html: false,
+            wikilinks: false,
         }
     }
 
@@ -54,11 +58,20 @@ impl Convertor {
         self
     }
 
+    /// Resolve Obsidian-style `[[wikilinks]]` in Markdown files against the other files under
+    /// `self.path`, as `StaticMarkdown::with_wikilinks` does for a single file. Disabled by
+    /// default.
+    pub fn with_wikilinks(mut self, yes: bool) -> Self {
+        self.wikilinks = yes;
+        self
+    }
+
     /// Processes all files in `path` and outputs `<name>.rs.inc` in `var!(OUT_DIR)`.
     ///
     /// Designed to be used in build.rs
     pub fn process(self) -> Result {
         let out_dir = env::var_os("OUT_DIR").context("are we not in a `build.rs` script?")?;
+        let known_targets = Rc::new(self.known_targets()?);
         // TODO support symlinks
         for entry in WalkDir::new(&self.path) {
             let entry =
@@ -89,7 +102,13 @@ impl Convertor {
                         write!(out, "{}", static_html.gen_dominator())?;
                     }
                     Some(e) if e == OsStr::new("md") => {
-                        let md_parser = StaticMarkdown::from_str(&contents);
+                        let rewriter = LinkRewriter::new(path_strip.to_owned(), known_targets.clone());
+                        let mut md_parser =
+                            StaticMarkdown::from_str(&contents).with_link_rewriter(rewriter);
+                        if self.wikilinks {
+                            md_parser = md_parser
+                                .with_wikilinks(WikilinkResolver::new(known_targets.clone()));
+                        }
                         let mut out = io::BufWriter::new(File::create(path_out)?);
                         md_parser.generate_dominator(&mut out)?;
                     }
@@ -112,4 +131,28 @@ impl Convertor {
         }
         Ok(())
     }
+
+    /// Paths, relative to `self.path`, of every file that `process` will convert. Walked
+    /// up-front so Markdown links can be rewritten against the full site before any file is
+    /// converted.
+    fn known_targets(&self) -> Result<HashSet<PathBuf>> {
+        let mut targets = HashSet::new();
+        for entry in WalkDir::new(&self.path) {
+            let entry =
+                entry.with_context(|| format!("walking through {}", self.path.display()))?;
+            let converted = matches!(
+                entry.path().extension().and_then(OsStr::to_str),
+                Some("html" | "htm" | "md")
+            );
+            if entry.file_type().is_file() && converted {
+                let path_strip = entry
+                    .path()
+                    .strip_prefix(&self.path)
+                    .context("entry in WalkDir not a child of base path")
+                    .context("internal error, please report as issue")?;
+                targets.insert(path_strip.to_owned());
+            }
+        }
+        Ok(targets)
+    }
 }
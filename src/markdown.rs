@@ -1,32 +1,554 @@
-use pulldown_cmark::{Event, HeadingLevel, Parser, Tag};
-use std::{fmt, io};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use pulldown_cmark::{Alignment, CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag};
+use regex::{Captures, Regex};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt, io,
+    io::Write as _,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{Color, Theme, ThemeSet},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
 
-pub struct StaticMarkdown<'input> {
-    parser: Parser<'input, 'input>,
+/// Default syntect theme used to highlight fenced code blocks, see [`StaticMarkdown::with_theme`].
+pub const DEFAULT_THEME: &str = "InspiredGitHub";
+
+pub struct StaticMarkdown {
+    input: String,
+    with_toc: bool,
+    highlight: bool,
+    theme: String,
+    link_rewriter: Option<LinkRewriter>,
+    wikilinks: Option<WikilinkResolver>,
 }
 
-impl<'input> StaticMarkdown<'input> {
-    pub fn from_str(input: &'input str) -> Self {
-        use pulldown_cmark::Options;
+impl StaticMarkdown {
+    pub fn from_str(input: &str) -> Self {
         StaticMarkdown {
-            parser: Parser::new_ext(input, Options::all()),
+            input: input.to_owned(),
+            with_toc: false,
+            highlight: true,
+            theme: DEFAULT_THEME.to_owned(),
+            link_rewriter: None,
+            wikilinks: None,
         }
     }
 
-    pub fn generate_dominator(self, writer: &'input mut impl io::Write) -> io::Result<()> {
+    /// When enabled, [`generate_dominator_with_toc`](Self::generate_dominator_with_toc) also
+    /// emits a nested table of contents. Headings get collision-safe anchor ids either way.
+    pub fn with_toc(mut self, yes: bool) -> Self {
+        self.with_toc = yes;
+        self
+    }
+
+    /// Enable or disable `syntect` syntax highlighting of fenced code blocks. Enabled by default;
+    /// disable for minimal, unstyled `code` output.
+    pub fn with_highlighting(mut self, yes: bool) -> Self {
+        self.highlight = yes;
+        self
+    }
+
+    /// Select the `syntect` theme used to highlight fenced code blocks, e.g.
+    /// `"Solarized (dark)"` or `"base16-ocean.dark"`. Defaults to [`DEFAULT_THEME`].
+    pub fn with_theme(mut self, theme: impl Into<String>) -> Self {
+        self.theme = theme.into();
+        self
+    }
+
+    /// Rewrite relative links that point at another converted `.md`/`.html` file under
+    /// `rewriter`'s `known_targets`. Unset by default, in which case link targets are emitted
+    /// verbatim.
+    pub fn with_link_rewriter(mut self, rewriter: LinkRewriter) -> Self {
+        self.link_rewriter = Some(rewriter);
+        self
+    }
+
+    /// Recognise Obsidian-style `[[target]]`, `[[target|label]]`, and `[[target#heading]]` text
+    /// before normal parsing, resolving `target` against `resolver`'s known site files. Unset by
+    /// default, in which case `[[...]]` text is left untouched.
+    pub fn with_wikilinks(mut self, resolver: WikilinkResolver) -> Self {
+        self.wikilinks = Some(resolver);
+        self
+    }
+
+    pub fn generate_dominator(self, writer: &mut impl io::Write) -> io::Result<()> {
+        let (input, parts) = self.into_parts();
+        let processed = parts.preprocess(&input);
+        parts.into_writer(&processed, writer).fmt()
+    }
+
+    /// As [`generate_dominator`](Self::generate_dominator), but also writes a nested `ul`/`li`/`a`
+    /// table of contents (keyed off heading anchor ids) to `toc_writer`, which the caller can
+    /// render wherever makes sense on the page, e.g. above the content.
+    pub fn generate_dominator_with_toc(
+        self,
+        writer: &mut impl io::Write,
+        toc_writer: &mut impl io::Write,
+    ) -> io::Result<()> {
+        let (input, parts) = self.into_parts();
+        let processed = parts.preprocess(&input);
+        let mut writer = parts.into_writer(&processed, writer);
+        writer.fmt()?;
+        write_toc(&writer.toc, toc_writer)
+    }
+
+    /// Splits off `input` so it can be preprocessed (and borrowed from) independently of the
+    /// remaining builder options, which `StaticMarkdownWriter` needs to own.
+    fn into_parts(self) -> (String, StaticMarkdownParts) {
+        (
+            self.input,
+            StaticMarkdownParts {
+                with_toc: self.with_toc,
+                highlight: self.highlight,
+                theme: self.theme,
+                link_rewriter: self.link_rewriter,
+                wikilinks: self.wikilinks,
+            },
+        )
+    }
+}
+
+/// `StaticMarkdown`'s builder options, minus `input`, so `input` can be preprocessed and borrowed
+/// from while these are consumed to build the `StaticMarkdownWriter`.
+struct StaticMarkdownParts {
+    with_toc: bool,
+    highlight: bool,
+    theme: String,
+    link_rewriter: Option<LinkRewriter>,
+    wikilinks: Option<WikilinkResolver>,
+}
+
+impl StaticMarkdownParts {
+    /// Applies wikilink rewriting (if enabled) to `input`, otherwise returns it unchanged.
+    fn preprocess<'input>(&self, input: &'input str) -> Cow<'input, str> {
+        match &self.wikilinks {
+            Some(resolver) => Cow::Owned(resolver.preprocess(input)),
+            None => Cow::Borrowed(input),
+        }
+    }
+
+    fn into_writer<'input, W>(
+        self,
+        input: &'input str,
+        writer: &'input mut W,
+    ) -> StaticMarkdownWriter<'input, W> {
+        let highlighter = if self.highlight {
+            Some(Highlighter::new(&self.theme))
+        } else {
+            None
+        };
         StaticMarkdownWriter {
-            parser: self.parser,
-            writer,
+            parser: Parser::new_ext(input, Options::all()).peekable(),
+            writer: Sink::Writer(writer),
             indent: 0,
+            table_alignments: Vec::new(),
+            table_col: 0,
+            table_in_head: false,
+            table_body_open: false,
+            in_heading: false,
+            heading_level: HeadingLevel::H1,
+            heading_text: String::new(),
+            pending_sink: None,
+            used_slugs: HashSet::new(),
+            with_toc: self.with_toc,
+            toc: Vec::new(),
+            in_code_block: false,
+            code_lang: None,
+            code_text: String::new(),
+            highlighter,
+            link_rewriter: self.link_rewriter,
+            footnote_order: Vec::new(),
+            footnote_seen: HashSet::new(),
+            footnote_defs: HashMap::new(),
+            current_footnote: None,
+            footnote_pending_sink: None,
+        }
+    }
+}
+
+/// Resolves relative Markdown links against the site's known converted files, rewriting them to
+/// the corresponding output route.
+pub struct LinkRewriter {
+    /// Path, relative to the site root, of the file currently being converted.
+    current_file: PathBuf,
+    /// Every file path (relative to the site root) that will be converted, used to recognise
+    /// intra-site links.
+    known_targets: Rc<HashSet<PathBuf>>,
+    /// Extension substituted in for a rewritten link's original `.md`/`.html`. Defaults to `html`.
+    route_extension: String,
+    /// Prefix prepended to a rewritten link, e.g. `"/docs"`. Unset by default.
+    route_prefix: Option<String>,
+}
+
+impl LinkRewriter {
+    pub fn new(current_file: impl Into<PathBuf>, known_targets: Rc<HashSet<PathBuf>>) -> Self {
+        LinkRewriter {
+            current_file: current_file.into(),
+            known_targets,
+            route_extension: "html".to_owned(),
+            route_prefix: None,
+        }
+    }
+
+    /// Extension substituted in for a rewritten link's original `.md`/`.html`. Defaults to
+    /// `"html"`.
+    pub fn with_route_extension(mut self, ext: impl Into<String>) -> Self {
+        self.route_extension = ext.into();
+        self
+    }
+
+    /// Prefix prepended to a rewritten link, e.g. `"/docs"`. Unset by default.
+    pub fn with_route_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.route_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Rewrites `target` if it's a relative link to another known, converted file; otherwise
+    /// (external `http(s):`/`mailto:` links, anchor-only links, or links to unknown files)
+    /// returns it unchanged.
+    fn rewrite(&self, target: &str) -> String {
+        if is_external_link(target) {
+            return target.to_owned();
+        }
+        let (path_part, fragment) = match target.split_once('#') {
+            Some((path, frag)) => (path, Some(frag)),
+            None => (target, None),
+        };
+        if path_part.is_empty() {
+            return target.to_owned();
+        }
+
+        let base_dir = self.current_file.parent().unwrap_or_else(|| Path::new(""));
+        let resolved = normalize_path(&base_dir.join(path_part));
+        if !self.known_targets.contains(&resolved) {
+            return target.to_owned();
+        }
+
+        let mut rewritten = route_for(&resolved, &self.route_extension, self.route_prefix.as_deref());
+        if let Some(frag) = fragment {
+            rewritten.push('#');
+            rewritten.push_str(frag);
+        }
+        rewritten
+    }
+}
+
+/// Substitutes `route_extension` for `target`'s own extension and prepends `route_prefix`, if
+/// set, producing the route a converted file is served at. Shared by `LinkRewriter::rewrite` and
+/// `WikilinkResolver::replace_one`, which both resolve a link to a known, converted file and then
+/// need this same route.
+fn route_for(target: &Path, route_extension: &str, route_prefix: Option<&str>) -> String {
+    let mut route = target.to_owned();
+    route.set_extension(route_extension);
+    match route_prefix {
+        Some(prefix) => format!("{}/{}", prefix.trim_end_matches('/'), route.display()),
+        None => route.display().to_string(),
+    }
+}
+
+/// Matches Obsidian-style `[[target]]`, `[[target|label]]`, and `[[target#heading]]` text.
+const WIKILINK_OUTER: &str = r"\[\[([^\]]+)\]\]";
+/// Splits a wikilink's inner text into its `file`, `frag`, and `label` parts.
+const WIKILINK_INNER: &str = r"^(?P<file>[^#|]+)(#(?P<frag>.+?))?(\|(?P<label>.+?))?$";
+/// Characters left unescaped in a percent-encoded route, so it still reads as a path.
+const PATH_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'/')
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.');
+
+/// Resolves Obsidian-style `[[wikilinks]]` against the site's known converted files, rewriting
+/// them to standard Markdown links before normal parsing.
+pub struct WikilinkResolver {
+    /// Every file path (relative to the site root) that will be converted, used to resolve
+    /// `[[target]]` by basename.
+    known_targets: Rc<HashSet<PathBuf>>,
+    /// Extension substituted in for a resolved target's original `.md`/`.html`. Defaults to
+    /// `html`, matching `LinkRewriter`'s default so the two agree on a target's route.
+    route_extension: String,
+    /// Prefix prepended to a resolved route, e.g. `"/docs"`. Unset by default.
+    route_prefix: Option<String>,
+}
+
+impl WikilinkResolver {
+    pub fn new(known_targets: Rc<HashSet<PathBuf>>) -> Self {
+        WikilinkResolver {
+            known_targets,
+            route_extension: "html".to_owned(),
+            route_prefix: None,
+        }
+    }
+
+    /// Extension substituted in for a resolved target's original `.md`/`.html`. Defaults to
+    /// `"html"`.
+    pub fn with_route_extension(mut self, ext: impl Into<String>) -> Self {
+        self.route_extension = ext.into();
+        self
+    }
+
+    /// Prefix prepended to a resolved route, e.g. `"/docs"`. Unset by default.
+    pub fn with_route_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.route_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Rewrites every `[[...]]` run in `input` into a standard Markdown link. A wikilink whose
+    /// target can't be resolved is left as plain text (its label, or the target itself) and a
+    /// warning is printed, so a stale link doesn't fail the whole build.
+    fn preprocess(&self, input: &str) -> String {
+        let outer = Regex::new(WIKILINK_OUTER).expect("WIKILINK_OUTER is a valid regex");
+        let inner = Regex::new(WIKILINK_INNER).expect("WIKILINK_INNER is a valid regex");
+        outer
+            .replace_all(input, |caps: &Captures| self.replace_one(&inner, &caps[1]))
+            .into_owned()
+    }
+
+    /// Renders a single wikilink's inner text (the part between `[[` and `]]`).
+    fn replace_one(&self, inner: &Regex, spec: &str) -> String {
+        let caps = match inner.captures(spec) {
+            Some(caps) => caps,
+            None => {
+                eprintln!("warning: malformed wikilink [[{}]], leaving as text", spec);
+                return spec.to_owned();
+            }
+        };
+        let file = caps.name("file").expect("file is required by WIKILINK_INNER").as_str();
+        let frag = caps.name("frag").map(|m| m.as_str());
+        let label = caps.name("label").map(|m| m.as_str()).unwrap_or(file);
+
+        match self.resolve(file) {
+            Some(target) => {
+                // Build the route (extension substitution + prefix) from the raw, unencoded
+                // path first, matching what `LinkRewriter::rewrite` would produce for the same
+                // target; only the finished route is percent-encoded, so it still matches
+                // `known_targets`' raw entries and gets the right extension.
+                let route = route_for(&target, &self.route_extension, self.route_prefix.as_deref());
+                let mut href = utf8_percent_encode(&route, PATH_ENCODE_SET).to_string();
+                if let Some(frag) = frag {
+                    href.push('#');
+                    href.push_str(&base_slug(frag));
+                }
+                format!("[{}]({})", label, href)
+            }
+            None => {
+                eprintln!("warning: unresolved wikilink target {:?}, leaving as text", file);
+                label.to_owned()
+            }
+        }
+    }
+
+    /// Looks `file` up against `known_targets` by basename, case-insensitively. If more than one
+    /// known target shares that basename, picks the lexicographically first deterministically
+    /// (rather than whatever `HashSet` iteration happens to yield first) and warns about the
+    /// ambiguity.
+    fn resolve(&self, file: &str) -> Option<PathBuf> {
+        let file_lower = file.to_ascii_lowercase();
+        let mut matches: Vec<&PathBuf> = self
+            .known_targets
+            .iter()
+            .filter(|target| {
+                target
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_ascii_lowercase() == file_lower)
+                    .unwrap_or(false)
+            })
+            .collect();
+        matches.sort();
+        if matches.len() > 1 {
+            eprintln!(
+                "warning: wikilink target {:?} matches {} files, picking {} deterministically",
+                file,
+                matches.len(),
+                matches[0].display()
+            );
+        }
+        matches.into_iter().next().cloned()
+    }
+}
+
+fn is_external_link(target: &str) -> bool {
+    target.starts_with("http://")
+        || target.starts_with("https://")
+        || target.starts_with("mailto:")
+        || target.starts_with('#')
+}
+
+/// Collapses `.`/`..` components without touching the filesystem, since the paths involved
+/// (site-relative link targets) needn't exist as given.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::ParentDir => {
+                result.pop();
+            }
+            std::path::Component::CurDir => {}
+            other => result.push(other.as_os_str()),
         }
-        .fmt()
     }
+    result
+}
+
+/// Void elements never carry a matching close tag, so they don't affect `html_tag_balance`.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Matches a single HTML tag, to tell an opening tag (which needs a later close) apart from a
+/// closing or self-closing one.
+const HTML_TAG: &str = r"<(/)?([a-zA-Z][a-zA-Z0-9-]*)(?:[^>]*?)(/)?>";
+
+/// Net change in "tags opened but not yet closed" contributed by `fragment`: `+1` per opening
+/// tag, `-1` per closing tag, `0` for self-closing tags and void elements (e.g. `<br>`).
+fn html_tag_balance(fragment: &str) -> i32 {
+    static TAG: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
+    let tag = TAG.get_or_init(|| Regex::new(HTML_TAG).expect("HTML_TAG is a valid regex"));
+    let mut balance = 0;
+    for caps in tag.captures_iter(fragment) {
+        if caps.get(1).is_some() {
+            balance -= 1;
+        } else if caps.get(3).is_none()
+            && !VOID_ELEMENTS.contains(&caps[2].to_ascii_lowercase().as_str())
+        {
+            balance += 1;
+        }
+    }
+    balance
+}
+
+/// Holds the loaded `syntect` syntax/theme definitions used to highlight fenced code blocks.
+struct Highlighter {
+    syntax_set: SyntaxSet,
+    theme: Theme,
+}
+
+impl Highlighter {
+    fn new(theme_name: &str) -> Self {
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let mut theme_set = ThemeSet::load_defaults();
+        let theme = theme_set
+            .themes
+            .remove(theme_name)
+            .unwrap_or_else(|| theme_set.themes.remove(DEFAULT_THEME).expect("default theme present"));
+        Highlighter { syntax_set, theme }
+    }
+
+    /// Highlights `code` as `lang`, returning `(css color, text)` spans in source order, or
+    /// `None` if `lang` isn't a syntax `syntect` recognises.
+    fn highlight<'c>(&self, lang: &str, code: &'c str) -> Option<Vec<(String, &'c str)>> {
+        // Fences commonly carry rustdoc-style attributes after the language, e.g.
+        // `rust,ignore`/`rust,no_run`; only the first token names the syntax.
+        let lang = lang
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .next()
+            .unwrap_or(lang);
+        let syntax = self.syntax_set.find_syntax_by_token(lang)?;
+        let mut h = HighlightLines::new(syntax, &self.theme);
+        let mut spans = Vec::new();
+        for line in LinesWithEndings::from(code) {
+            let ranges = h.highlight_line(line, &self.syntax_set).ok()?;
+            for (style, text) in ranges {
+                spans.push((color_css(style.foreground), text));
+            }
+        }
+        Some(spans)
+    }
+}
+
+fn color_css(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+/// A sink that writes either straight to the caller's writer, or into an in-memory buffer so a
+/// heading's opening tag can be rewritten (with its anchor `id`) once its text is fully known.
+enum Sink<'a, W> {
+    Writer(&'a mut W),
+    Buffer(Vec<u8>),
+}
+
+impl<'a, W> io::Write for Sink<'a, W>
+where
+    W: io::Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Sink::Writer(w) => w.write(buf),
+            Sink::Buffer(b) => b.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Sink::Writer(w) => w.flush(),
+            Sink::Buffer(b) => b.flush(),
+        }
+    }
+}
+
+struct TocEntry {
+    level: HeadingLevel,
+    slug: String,
+    text: String,
 }
 
 pub struct StaticMarkdownWriter<'a, W> {
-    parser: Parser<'a, 'a>,
-    writer: &'a mut W,
+    /// Peekable so a run of consecutive `Event::Html`s (pulldown-cmark splits a multi-line HTML
+    /// block into one event per line) can be buffered and parsed as a single document.
+    parser: std::iter::Peekable<Parser<'a, 'a>>,
+    writer: Sink<'a, W>,
     indent: u32,
+    /// Column alignments for the table currently being written, set by `Tag::Table`.
+    table_alignments: Vec<Alignment>,
+    /// Index of the next cell to be written within the current table row.
+    table_col: usize,
+    /// Whether we're between `Tag::TableHead` start/end (cells render as `th` not `td`).
+    table_in_head: bool,
+    /// Whether the `tbody` for the current table has been opened yet.
+    table_body_open: bool,
+    /// Whether we're between `Tag::Heading` start/end, buffering its rendered content.
+    in_heading: bool,
+    heading_level: HeadingLevel,
+    /// Plain text accumulated from the heading's `Event::Text`s, used to derive its slug.
+    heading_text: String,
+    /// The real sink, parked here while `self.writer` is swapped for a heading's buffer.
+    pending_sink: Option<Sink<'a, W>>,
+    /// Every final slug already handed out, so a repeat `base_slug` can find the next free
+    /// `-N` suffix instead of colliding with a heading that already claimed it.
+    used_slugs: HashSet<String>,
+    with_toc: bool,
+    toc: Vec<TocEntry>,
+    /// Whether we're between `Tag::CodeBlock` start/end, buffering its raw text.
+    in_code_block: bool,
+    /// The fenced language hint, e.g. `Some("rust")`, or `None` for unfenced/unlabelled blocks.
+    code_lang: Option<String>,
+    /// Raw text accumulated from the code block's `Event::Text`s.
+    code_text: String,
+    /// `None` when highlighting is disabled via `StaticMarkdown::with_highlighting(false)`.
+    highlighter: Option<Highlighter>,
+    /// `None` unless `StaticMarkdown::with_link_rewriter` was called.
+    link_rewriter: Option<LinkRewriter>,
+    /// Footnote names in first-reference order, used to number markers and to order the
+    /// footnotes section emitted at the end of the document.
+    footnote_order: Vec<String>,
+    /// Names already added to `footnote_order`, so a footnote referenced more than once is only
+    /// numbered and listed once.
+    footnote_seen: HashSet<String>,
+    /// Rendered dominator output of each `Tag::FootnoteDefinition`, keyed by name. Definitions can
+    /// appear anywhere in the source, so these are buffered and flushed in `footnote_order` once
+    /// the main loop completes.
+    footnote_defs: HashMap<String, Vec<u8>>,
+    /// Name of the `Tag::FootnoteDefinition` currently being buffered, if any.
+    current_footnote: Option<String>,
+    /// The real sink, parked here while `self.writer` is swapped for a footnote definition's
+    /// buffer.
+    footnote_pending_sink: Option<Sink<'a, W>>,
 }
 
 impl<'a, W> StaticMarkdownWriter<'a, W>
@@ -37,20 +559,32 @@ where
         while let Some(event) = self.parser.next() {
             self.fmt_event(event)?;
         }
-        Ok(())
+        self.write_footnotes()
     }
 
     fn fmt_event(&mut self, evt: Event<'a>) -> io::Result<()> {
         match evt {
             Event::Start(tag) => self.fmt_start_event(tag),
             Event::End(tag) => self.fmt_end_event(tag),
-            Event::Text(text) => writeln!(
-                self.writer,
-                "{}.text(\"{}\")",
-                indent(self.indent),
-                text.escape_debug()
-            ),
+            Event::Text(text) => {
+                if self.in_heading {
+                    self.heading_text.push_str(&text);
+                }
+                if self.in_code_block {
+                    self.code_text.push_str(&text);
+                    return Ok(());
+                }
+                writeln!(
+                    self.writer,
+                    "{}.text(\"{}\")",
+                    indent(self.indent),
+                    text.escape_debug()
+                )
+            }
             Event::Code(text) => {
+                if self.in_heading {
+                    self.heading_text.push_str(&text);
+                }
                 writeln!(
                     self.writer,
                     "{}.child(::dominator::html!(\"code\") {{\n}})",
@@ -64,8 +598,75 @@ where
                 )?;
                 writeln!(self.writer, "{}}})", indent(self.indent))
             }
-            Event::Html(_html) => Ok(()),
-            Event::FootnoteReference(_tag) => Ok(()),
+            Event::Html(html) => {
+                // Inline raw HTML (e.g. `<b>raw</b>` inside a sentence) arrives as separate,
+                // non-adjacent `Html` events with the tag's own content as a `Text` event in
+                // between, not just consecutive `Html`s as multi-line blocks do. Keep consuming
+                // events of either kind until every opened tag has a matching close, so
+                // `StaticHtml::from_str` below sees a complete, balanced fragment. A run of
+                // consecutive `Html`s is always taken whole, balanced or not.
+                let mut raw = html.into_string();
+                let mut balance = html_tag_balance(&raw);
+                loop {
+                    match self.parser.peek() {
+                        Some(Event::Html(_)) => {
+                            if let Some(Event::Html(more)) = self.parser.next() {
+                                balance += html_tag_balance(&more);
+                                raw.push_str(&more);
+                            }
+                        }
+                        Some(Event::Text(_)) if balance > 0 => {
+                            if let Some(Event::Text(text)) = self.parser.next() {
+                                raw.push_str(&text);
+                            }
+                        }
+                        Some(Event::SoftBreak) if balance > 0 => {
+                            self.parser.next();
+                            raw.push('\n');
+                        }
+                        Some(Event::HardBreak) if balance > 0 => {
+                            self.parser.next();
+                            raw.push_str("<br>\n");
+                        }
+                        _ => break,
+                    }
+                }
+                self.splice_html(&raw)
+            }
+            Event::FootnoteReference(name) => {
+                let name = name.into_string();
+                let (n, is_first) = self.footnote_number(&name);
+                writeln!(
+                    self.writer,
+                    "{}.child(::dominator::html!(\"sup\", {{",
+                    indent(self.indent)
+                )?;
+                writeln!(
+                    self.writer,
+                    "{}.child(::dominator::html!(\"a\", {{",
+                    indent(self.indent + 1)
+                )?;
+                // Only the first occurrence of a repeated `[^name]` gets the `fnref-` id, so the
+                // footnote's back-reference link (which always points at `#fnref-<name>`) has a
+                // single, unambiguous target, and `id`s stay unique.
+                if is_first {
+                    writeln!(
+                        self.writer,
+                        "{}.attr(\"id\", \"fnref-{}\")",
+                        indent(self.indent + 2),
+                        name.escape_debug()
+                    )?;
+                }
+                writeln!(
+                    self.writer,
+                    "{}.attr(\"href\", \"#fn-{}\")",
+                    indent(self.indent + 2),
+                    name.escape_debug()
+                )?;
+                writeln!(self.writer, "{}.text(\"{}\")", indent(self.indent + 2), n)?;
+                writeln!(self.writer, "{}}})", indent(self.indent + 1))?;
+                writeln!(self.writer, "{}}})", indent(self.indent))
+            }
             Event::SoftBreak => Ok(()),
             Event::HardBreak => Ok(()),
             Event::Rule => write!(self.writer, ".child(::dominator::html!(\"hr\"))"),
@@ -79,16 +680,23 @@ where
         match tag {
             Tag::Paragraph => writeln!(self.writer, ".child(::dominator::html!(\"p\"), {{"),
             Tag::Heading(level, _, _) => {
-                writeln!(
-                    self.writer,
-                    "{}.child(::dominator::html!(\"{}\", {{",
-                    indent(self.indent),
-                    conv_heading(level)
-                )
+                // Deferred: we don't know the slug for the `id` attribute until we've seen the
+                // heading's full text, so buffer everything until `End(Heading)`.
+                self.in_heading = true;
+                self.heading_level = level;
+                self.heading_text.clear();
+                self.pending_sink = Some(std::mem::replace(&mut self.writer, Sink::Buffer(Vec::new())));
+                Ok(())
             }
             // TODO pre isn't really appropriate here
             Tag::BlockQuote => writeln!(self.writer, ".child(::dominator::html!(\"pre\"), {{"),
-            Tag::CodeBlock(_kind) => {
+            Tag::CodeBlock(kind) => {
+                self.in_code_block = true;
+                self.code_lang = match kind {
+                    CodeBlockKind::Fenced(lang) if !lang.is_empty() => Some(lang.into_string()),
+                    _ => None,
+                };
+                self.code_text.clear();
                 writeln!(
                     self.writer,
                     "{}.child(::dominator::html!(\"code\"), {{",
@@ -118,29 +726,112 @@ where
                 "{}.child(::dominator::html!(\"li\"), {{",
                 indent(self.indent)
             ),
-            Tag::FootnoteDefinition(_text) => Ok(()),
-            Tag::Table(_alignment) => Ok(()),
-            Tag::TableHead => Ok(()),
-            Tag::TableRow => Ok(()),
-            Tag::TableCell => Ok(()),
-            Tag::Emphasis => Ok(()),
-            Tag::Strong => Ok(()),
-            Tag::Strikethrough => Ok(()),
+            Tag::FootnoteDefinition(name) => {
+                // Deferred: a definition can appear anywhere in the source (often out of order
+                // relative to its reference), so buffer its content and flush it from
+                // `write_footnotes` once the main loop completes.
+                self.current_footnote = Some(name.into_string());
+                self.footnote_pending_sink =
+                    Some(std::mem::replace(&mut self.writer, Sink::Buffer(Vec::new())));
+                Ok(())
+            }
+            Tag::Table(alignments) => {
+                self.table_alignments = alignments;
+                self.table_col = 0;
+                self.table_body_open = false;
+                writeln!(
+                    self.writer,
+                    "{}.child(::dominator::html!(\"table\", {{",
+                    indent(self.indent)
+                )
+            }
+            Tag::TableHead => {
+                self.table_in_head = true;
+                self.table_col = 0;
+                writeln!(
+                    self.writer,
+                    "{}.child(::dominator::html!(\"thead\", {{",
+                    indent(self.indent)
+                )?;
+                writeln!(
+                    self.writer,
+                    "{}.child(::dominator::html!(\"tr\", {{",
+                    indent(self.indent + 1)
+                )
+            }
+            Tag::TableRow => {
+                self.table_col = 0;
+                if !self.table_body_open {
+                    self.table_body_open = true;
+                    writeln!(
+                        self.writer,
+                        "{}.child(::dominator::html!(\"tbody\", {{",
+                        indent(self.indent)
+                    )?;
+                }
+                writeln!(
+                    self.writer,
+                    "{}.child(::dominator::html!(\"tr\", {{",
+                    indent(self.indent + 1)
+                )
+            }
+            Tag::TableCell => {
+                let tag_name = if self.table_in_head { "th" } else { "td" };
+                writeln!(
+                    self.writer,
+                    "{}.child(::dominator::html!(\"{}\", {{",
+                    indent(self.indent + 1),
+                    tag_name
+                )?;
+                if let Some(css) = self
+                    .table_alignments
+                    .get(self.table_col)
+                    .and_then(|align| align_css(*align))
+                {
+                    writeln!(
+                        self.writer,
+                        "{}.style(\"text-align\", \"{}\")",
+                        indent(self.indent + 2),
+                        css
+                    )?;
+                }
+                self.table_col += 1;
+                Ok(())
+            }
+            Tag::Emphasis => writeln!(
+                self.writer,
+                "{}.child(::dominator::html!(\"em\", {{",
+                indent(self.indent)
+            ),
+            Tag::Strong => writeln!(
+                self.writer,
+                "{}.child(::dominator::html!(\"strong\", {{",
+                indent(self.indent)
+            ),
+            Tag::Strikethrough => writeln!(
+                self.writer,
+                "{}.child(::dominator::html!(\"del\", {{",
+                indent(self.indent)
+            ),
             Tag::Link(_ty, to, title) => {
+                let href = match &self.link_rewriter {
+                    Some(rewriter) => rewriter.rewrite(&to),
+                    None => to.into_string(),
+                };
                 writeln!(
                     self.writer,
-                    "{}.child(::dominator::html(\"a\"), {{",
+                    "{}.child(::dominator::html!(\"a\", {{",
                     indent(self.indent)
                 )?;
                 writeln!(
                     self.writer,
-                    "{}.attr(\"html\", \"{}\"",
+                    "{}.attr(\"href\", \"{}\")",
                     indent(self.indent + 1),
-                    to.escape_debug()
+                    href.escape_debug()
                 )?;
                 writeln!(
                     self.writer,
-                    "{}.attr(\"title\", \"{}\"",
+                    "{}.attr(\"title\", \"{}\")",
                     indent(self.indent + 1),
                     title.escape_debug()
                 )
@@ -173,23 +864,317 @@ where
         self.indent -= 1;
         match tag {
             Tag::Paragraph => writeln!(self.writer, "{}}})", indent(self.indent)),
-            Tag::Heading(_, _, _) => writeln!(self.writer, "{}}})", indent(self.indent)),
+            Tag::Heading(level, _, _) => {
+                self.in_heading = false;
+                let slug = self.slugify(&self.heading_text.clone());
+                let buffered = match std::mem::replace(
+                    &mut self.writer,
+                    self.pending_sink.take().expect("heading sink was parked on start"),
+                ) {
+                    Sink::Buffer(buf) => buf,
+                    Sink::Writer(_) => unreachable!("heading sink was swapped for a buffer"),
+                };
+                writeln!(
+                    self.writer,
+                    "{}.child(::dominator::html!(\"{}\", {{",
+                    indent(self.indent),
+                    conv_heading(level)
+                )?;
+                writeln!(
+                    self.writer,
+                    "{}.attr(\"id\", \"{}\")",
+                    indent(self.indent + 1),
+                    slug
+                )?;
+                self.writer.write_all(&buffered)?;
+                if self.with_toc {
+                    self.toc.push(TocEntry {
+                        level,
+                        slug,
+                        text: self.heading_text.clone(),
+                    });
+                }
+                writeln!(self.writer, "{}}})", indent(self.indent))
+            }
             Tag::BlockQuote => writeln!(self.writer, "{}}})", indent(self.indent)),
-            Tag::CodeBlock(_) => writeln!(self.writer, "{}}})", indent(self.indent)),
+            Tag::CodeBlock(_) => {
+                self.in_code_block = false;
+                let spans = self
+                    .code_lang
+                    .as_deref()
+                    .zip(self.highlighter.as_ref())
+                    .and_then(|(lang, highlighter)| highlighter.highlight(lang, &self.code_text));
+                match spans {
+                    Some(spans) => {
+                        for (css_color, text) in spans {
+                            writeln!(
+                                self.writer,
+                                "{}.child(::dominator::html!(\"span\", {{",
+                                indent(self.indent + 1)
+                            )?;
+                            writeln!(
+                                self.writer,
+                                "{}.style(\"color\", \"{}\")",
+                                indent(self.indent + 2),
+                                css_color
+                            )?;
+                            writeln!(
+                                self.writer,
+                                "{}.text(\"{}\")",
+                                indent(self.indent + 2),
+                                text.escape_debug()
+                            )?;
+                            writeln!(self.writer, "{}}})", indent(self.indent + 1))?;
+                        }
+                    }
+                    None => writeln!(
+                        self.writer,
+                        "{}.text(\"{}\")",
+                        indent(self.indent + 1),
+                        self.code_text.escape_debug()
+                    )?,
+                }
+                writeln!(self.writer, "{}}})", indent(self.indent))
+            }
             Tag::List(_) => writeln!(self.writer, "{}}})", indent(self.indent)),
             Tag::Item => writeln!(self.writer, "{}}})", indent(self.indent)),
-            Tag::FootnoteDefinition(_) => Ok(()),
-            Tag::Table(_) => Ok(()),
-            Tag::TableHead => Ok(()),
-            Tag::TableRow => Ok(()),
-            Tag::TableCell => Ok(()),
-            Tag::Emphasis => Ok(()),
-            Tag::Strong => Ok(()),
-            Tag::Strikethrough => Ok(()),
+            Tag::FootnoteDefinition(_) => {
+                let buffered = match std::mem::replace(
+                    &mut self.writer,
+                    self.footnote_pending_sink
+                        .take()
+                        .expect("footnote sink was parked on start"),
+                ) {
+                    Sink::Buffer(buf) => buf,
+                    Sink::Writer(_) => unreachable!("footnote sink was swapped for a buffer"),
+                };
+                if let Some(name) = self.current_footnote.take() {
+                    self.footnote_defs.insert(name, buffered);
+                }
+                Ok(())
+            }
+            Tag::Table(_) => {
+                if self.table_body_open {
+                    writeln!(self.writer, "{}}})", indent(self.indent + 1))?;
+                    self.table_body_open = false;
+                }
+                writeln!(self.writer, "{}}})", indent(self.indent))
+            }
+            Tag::TableHead => {
+                self.table_in_head = false;
+                writeln!(self.writer, "{}}})", indent(self.indent + 1))?;
+                writeln!(self.writer, "{}}})", indent(self.indent))
+            }
+            Tag::TableRow => writeln!(self.writer, "{}}})", indent(self.indent + 1)),
+            Tag::TableCell => writeln!(self.writer, "{}}})", indent(self.indent + 1)),
+            Tag::Emphasis => writeln!(self.writer, "{}}})", indent(self.indent)),
+            Tag::Strong => writeln!(self.writer, "{}}})", indent(self.indent)),
+            Tag::Strikethrough => writeln!(self.writer, "{}}})", indent(self.indent)),
             Tag::Link(_, _, _) => writeln!(self.writer, "{}}})", indent(self.indent)),
             Tag::Image(_, _, _) => Ok(()),
         }
     }
+
+    /// De-duplicates `base_slug(text)` against every slug emitted so far by appending `-N`,
+    /// trying increasing `N` until the candidate itself (not just its base) is unused.
+    fn slugify(&mut self, text: &str) -> String {
+        let base = base_slug(text);
+        let mut candidate = base.clone();
+        let mut n = 1;
+        while self.used_slugs.contains(&candidate) {
+            candidate = format!("{}-{}", base, n);
+            n += 1;
+        }
+        self.used_slugs.insert(candidate.clone());
+        candidate
+    }
+
+    /// Returns `name`'s 1-based footnote marker number and whether this is its first reference,
+    /// recording it in `footnote_order` the first time it's seen.
+    fn footnote_number(&mut self, name: &str) -> (usize, bool) {
+        let is_first = self.footnote_seen.insert(name.to_owned());
+        if is_first {
+            self.footnote_order.push(name.to_owned());
+        }
+        let n = self
+            .footnote_order
+            .iter()
+            .position(|seen| seen == name)
+            .expect("name was just inserted into footnote_order")
+            + 1;
+        (n, is_first)
+    }
+
+    /// Flushes buffered footnote definitions into an ordered footnotes section, each with a
+    /// back-reference link to its first `Event::FootnoteReference`; a definition with no
+    /// reference is rendered last, without one. No-op if the document neither references nor
+    /// defines any footnote.
+    fn write_footnotes(&mut self) -> io::Result<()> {
+        if self.footnote_order.is_empty() && self.footnote_defs.is_empty() {
+            return Ok(());
+        }
+        writeln!(
+            self.writer,
+            "{}.child(::dominator::html!(\"ol\", {{",
+            indent(self.indent)
+        )?;
+        for name in self.footnote_order.clone() {
+            writeln!(
+                self.writer,
+                "{}.child(::dominator::html!(\"li\", {{",
+                indent(self.indent + 1)
+            )?;
+            writeln!(
+                self.writer,
+                "{}.attr(\"id\", \"fn-{}\")",
+                indent(self.indent + 2),
+                name.escape_debug()
+            )?;
+            if let Some(buffered) = self.footnote_defs.remove(&name) {
+                self.writer.write_all(&buffered)?;
+            }
+            writeln!(
+                self.writer,
+                "{}.child(::dominator::html!(\"a\", {{",
+                indent(self.indent + 2)
+            )?;
+            writeln!(
+                self.writer,
+                "{}.attr(\"href\", \"#fnref-{}\")",
+                indent(self.indent + 3),
+                name.escape_debug()
+            )?;
+            writeln!(self.writer, "{}.text(\"↩\")", indent(self.indent + 3))?;
+            writeln!(self.writer, "{}}})", indent(self.indent + 2))?;
+            writeln!(self.writer, "{}}})", indent(self.indent + 1))?;
+        }
+        // Definitions with no matching reference never go through `footnote_order`, but they
+        // still get rendered (sorted for deterministic output), just without a back-reference
+        // link since there's no `fnref-` anchor for one to point at, matching the warn-and-degrade
+        // pattern `WikilinkResolver` uses for an unresolved target rather than dropping content.
+        let mut orphans: Vec<String> = self.footnote_defs.keys().cloned().collect();
+        orphans.sort();
+        for name in orphans {
+            eprintln!(
+                "warning: footnote definition [^{}] is never referenced, rendering without a back-reference",
+                name
+            );
+            writeln!(
+                self.writer,
+                "{}.child(::dominator::html!(\"li\", {{",
+                indent(self.indent + 1)
+            )?;
+            writeln!(
+                self.writer,
+                "{}.attr(\"id\", \"fn-{}\")",
+                indent(self.indent + 2),
+                name.escape_debug()
+            )?;
+            if let Some(buffered) = self.footnote_defs.remove(&name) {
+                self.writer.write_all(&buffered)?;
+            }
+            writeln!(self.writer, "{}}})", indent(self.indent + 1))?;
+        }
+        writeln!(self.writer, "{}}})", indent(self.indent))
+    }
+
+    /// Parses a run of raw HTML with the crate's own HTML converter and splices its generated
+    /// dominator children into the Markdown output at the current position, cross-linking the
+    /// `markdown` and `html` modules so a document mixing both produces one coherent tree.
+    fn splice_html(&mut self, raw: &str) -> io::Result<()> {
+        match crate::html::StaticHtml::from_str(raw, false) {
+            Ok(static_html) => write!(self.writer, "{}", static_html.gen_dominator()),
+            Err(err) => {
+                eprintln!("warning: skipping embedded HTML block, failed to parse: {}", err);
+                Ok(())
+            }
+        }
+    }
+}
+
+fn write_toc(entries: &[TocEntry], out: &mut impl io::Write) -> io::Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let mut depth = 0u32;
+    writeln!(out, "{}.child(::dominator::html!(\"ul\", {{", indent(depth))?;
+    depth += 1;
+    let mut stack = vec![heading_rank(entries[0].level)];
+    for entry in entries {
+        let rank = heading_rank(entry.level);
+        while *stack.last().unwrap() < rank {
+            writeln!(out, "{}.child(::dominator::html!(\"ul\", {{", indent(depth))?;
+            depth += 1;
+            stack.push(rank);
+        }
+        while stack.len() > 1 && *stack.last().unwrap() > rank {
+            depth -= 1;
+            writeln!(out, "{}}})", indent(depth))?;
+            stack.pop();
+        }
+        writeln!(out, "{}.child(::dominator::html!(\"li\", {{", indent(depth))?;
+        writeln!(
+            out,
+            "{}.child(::dominator::html!(\"a\", {{",
+            indent(depth + 1)
+        )?;
+        writeln!(
+            out,
+            "{}.attr(\"href\", \"#{}\")",
+            indent(depth + 2),
+            entry.slug
+        )?;
+        writeln!(
+            out,
+            "{}.text(\"{}\")",
+            indent(depth + 2),
+            entry.text.escape_debug()
+        )?;
+        writeln!(out, "{}}})", indent(depth + 1))?;
+        writeln!(out, "{}}})", indent(depth))?;
+    }
+    while !stack.is_empty() {
+        depth -= 1;
+        writeln!(out, "{}}})", indent(depth))?;
+        stack.pop();
+    }
+    Ok(())
+}
+
+/// Lowercase, whitespace-to-`-`, alphanumeric/`-`/`_`-only slug for `text`, with no
+/// de-duplication. Shared by [`StaticMarkdownWriter::slugify`]
+/// (which de-duplicates against headings already seen) and [`WikilinkResolver`] (which needs the
+/// bare slug a `#heading` fragment will resolve to, without tracking collisions of its own).
+fn base_slug(text: &str) -> String {
+    let mut base = String::with_capacity(text.len());
+    let mut pending_dash = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            pending_dash = !base.is_empty();
+        } else if c.is_alphanumeric() || c == '-' || c == '_' {
+            if pending_dash {
+                base.push('-');
+                pending_dash = false;
+            }
+            base.extend(c.to_lowercase());
+        }
+    }
+    if base.is_empty() {
+        base.push_str("section");
+    }
+    base
+}
+
+fn heading_rank(level: HeadingLevel) -> u8 {
+    use HeadingLevel::*;
+    match level {
+        H1 => 1,
+        H2 => 2,
+        H3 => 3,
+        H4 => 4,
+        H5 => 5,
+        H6 => 6,
+    }
 }
 
 fn conv_heading(level: HeadingLevel) -> &'static str {
@@ -204,6 +1189,15 @@ fn conv_heading(level: HeadingLevel) -> &'static str {
     }
 }
 
+fn align_css(align: Alignment) -> Option<&'static str> {
+    match align {
+        Alignment::None => None,
+        Alignment::Left => Some("left"),
+        Alignment::Center => Some("center"),
+        Alignment::Right => Some("right"),
+    }
+}
+
 fn indent(amt: u32) -> impl fmt::Display {
     struct Indent(u32);
 
@@ -218,3 +1212,101 @@ fn indent(amt: u32) -> impl fmt::Display {
 
     Indent(amt)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A heading's anchor id is its slug deduped against every id already handed out, not just
+    /// against other headings with the same text.
+    #[test]
+    fn heading_slugs_dedup_against_every_emitted_id() {
+        let input = "# Intro\n\n# Intro\n\n# Intro-1\n";
+        let mut out = Vec::new();
+        StaticMarkdown::from_str(input)
+            .generate_dominator(&mut out)
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains(".attr(\"id\", \"intro\")"));
+        assert!(out.contains(".attr(\"id\", \"intro-1\")"));
+        // The third heading's own text slugifies to `intro-1`, which the second heading already
+        // claimed, so it must fall through to the next free suffix instead of colliding.
+        assert!(out.contains(".attr(\"id\", \"intro-1-1\")"));
+    }
+
+    fn known(paths: &[&str]) -> Rc<HashSet<PathBuf>> {
+        Rc::new(paths.iter().map(PathBuf::from).collect())
+    }
+
+    #[test]
+    fn wikilink_plain_target_resolves_to_route() {
+        let resolver = WikilinkResolver::new(known(&["notes/foo.md"]));
+        assert_eq!(
+            resolver.preprocess("[[foo]]"),
+            "[foo](notes/foo.html)"
+        );
+    }
+
+    #[test]
+    fn wikilink_label_overrides_link_text() {
+        let resolver = WikilinkResolver::new(known(&["notes/foo.md"]));
+        assert_eq!(
+            resolver.preprocess("[[foo|Label]]"),
+            "[Label](notes/foo.html)"
+        );
+    }
+
+    #[test]
+    fn wikilink_fragment_is_slugified_and_appended() {
+        let resolver = WikilinkResolver::new(known(&["notes/foo.md"]));
+        assert_eq!(
+            resolver.preprocess("[[foo#My Heading]]"),
+            "[foo](notes/foo.html#my-heading)"
+        );
+    }
+
+    #[test]
+    fn wikilink_unresolved_target_falls_back_to_plain_text() {
+        let resolver = WikilinkResolver::new(known(&["notes/foo.md"]));
+        assert_eq!(resolver.preprocess("[[missing]]"), "missing");
+    }
+
+    #[test]
+    fn wikilink_basename_collision_picks_first_match_deterministically() {
+        let resolver = WikilinkResolver::new(known(&["sub/dup.md", "other/dup.md"]));
+        assert_eq!(resolver.preprocess("[[dup]]"), "[dup](other/dup.html)");
+    }
+
+    #[test]
+    fn rewrite_same_dir_link() {
+        let rewriter = LinkRewriter::new("docs/intro.md", known(&["docs/guide.md"]));
+        assert_eq!(rewriter.rewrite("guide.md"), "docs/guide.html");
+    }
+
+    #[test]
+    fn rewrite_parent_dir_link() {
+        let rewriter = LinkRewriter::new("docs/sub/page.md", known(&["docs/other.md"]));
+        assert_eq!(rewriter.rewrite("../other.md"), "docs/other.html");
+    }
+
+    #[test]
+    fn rewrite_leaves_external_links_untouched() {
+        let rewriter = LinkRewriter::new("docs/intro.md", known(&["docs/guide.md"]));
+        assert_eq!(rewriter.rewrite("https://example.com"), "https://example.com");
+        assert_eq!(rewriter.rewrite("mailto:a@b.com"), "mailto:a@b.com");
+    }
+
+    #[test]
+    fn rewrite_leaves_unknown_targets_untouched() {
+        let rewriter = LinkRewriter::new("docs/intro.md", known(&["docs/guide.md"]));
+        assert_eq!(rewriter.rewrite("missing.md"), "missing.md");
+    }
+
+    #[test]
+    fn normalize_path_above_root_collapses_instead_of_erroring() {
+        // `result.pop()` on an already-empty path is a no-op, so a `../` that walks above the
+        // site root is silently absorbed rather than rejected. Documented here as current
+        // behavior, not asserted as desirable.
+        assert_eq!(normalize_path(Path::new("../escaped.md")), PathBuf::from("escaped.md"));
+    }
+}